@@ -1,9 +1,9 @@
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::path::Path;
 
 // const BLOCK_SIZE: usize = 1024 * 1024; // 1MB
-const BLOCK_SIZE: usize = 2; // 2 bytes
+pub(crate) const BLOCK_SIZE: usize = 2; // 2 bytes
 
 pub struct BlockIterator<R: Read> {
     reader: R,
@@ -32,9 +32,59 @@ impl<R: Read> Iterator for BlockIterator<R> {
     }
 }
 
-/// Iterates over the given file in 1MB blocks.  
+/// Iterates over the given file in `BLOCK_SIZE`-byte blocks.
 /// Pass the file path, and this function returns a BlockIterator over its contents.
 pub fn iter_file_blocks<P: AsRef<Path>>(path: P) -> io::Result<BlockIterator<File>> {
     let file = File::open(path)?;
     Ok(BlockIterator::new(file))
-}
\ No newline at end of file
+}
+
+/// Yields a reader's blocks from the end of the file toward the start.
+///
+/// Each call to `next()` seeks to the start of the next (lower) block and
+/// reads it, so like `BlockIterator` it holds only one block in memory at a
+/// time. The leading (leftmost) block is clamped to offset 0 (or to the
+/// floor given to `with_floor`), so its size may be smaller than
+/// `BLOCK_SIZE` if the data length isn't a multiple of it.
+pub struct ReverseBlockIterator<R: Read + Seek> {
+    reader: R,
+    floor: u64,
+    next_end: u64,
+}
+
+impl<R: Read + Seek> ReverseBlockIterator<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        Self::with_floor(reader, 0)
+    }
+
+    /// Like `new`, but never reads before byte offset `floor`. Useful when
+    /// the file has a fixed-size header that isn't part of the data.
+    pub fn with_floor(mut reader: R, floor: u64) -> io::Result<Self> {
+        let size = reader.seek(SeekFrom::End(0))?;
+        Ok(ReverseBlockIterator { reader, floor, next_end: size })
+    }
+}
+
+impl<R: Read + Seek> Iterator for ReverseBlockIterator<R> {
+    // Each iteration returns a Result containing a vector of bytes
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_end <= self.floor {
+            return None;
+        }
+        let start = self.next_end.saturating_sub(BLOCK_SIZE as u64).max(self.floor);
+        let len = (self.next_end - start) as usize;
+
+        if let Err(e) = self.reader.seek(SeekFrom::Start(start)) {
+            return Some(Err(e));
+        }
+        let mut buffer = vec![0u8; len];
+        if let Err(e) = self.reader.read_exact(&mut buffer) {
+            return Some(Err(e));
+        }
+
+        self.next_end = start;
+        Some(Ok(buffer))
+    }
+}
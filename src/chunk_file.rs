@@ -0,0 +1,147 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+
+use memmap2::{MmapMut, MmapOptions};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// A chunked, memory-mapped backing store for a HugeUint-style number file.
+///
+/// `square_number`'s O(size^2) inner loop used to re-open the file and seek on
+/// every single chunk access. Mapping the whole file once turns every
+/// `chunk`/`write_chunk` call into a plain memory access instead of a syscall.
+///
+/// `chunk_size` is per-instance rather than a global constant: `pepin_test`
+/// needs the Fermat modulus to land exactly on a chunk boundary, which means
+/// picking a chunk size based on `n` rather than a single fixed one.
+pub(crate) struct ChunkFile {
+    file: File,
+    mmap: MmapMut,
+    chunk_size: usize,
+    num_blocks: usize,
+}
+
+impl ChunkFile {
+    /// Opens (creating if necessary) `filename` and sets its length to exactly
+    /// `num_blocks` chunks of `chunk_size` bytes each (at least one chunk).
+    /// The file is not truncated on open, so contents within that length are
+    /// preserved when re-opening a file after the rename-swap dance in
+    /// `pepin_test`; anything past that length is cut off.
+    pub(crate) fn open(filename: &str, chunk_size: usize, num_blocks: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(filename)?;
+        let num_blocks = num_blocks.max(1);
+        file.set_len((num_blocks * chunk_size) as u64)?;
+        let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+        Ok(ChunkFile { file, mmap, chunk_size, num_blocks })
+    }
+
+    /// Remaps the file after growing it, preserving existing contents.
+    fn grow_to(&mut self, num_blocks: usize) -> io::Result<()> {
+        if num_blocks <= self.num_blocks {
+            return Ok(());
+        }
+        self.file.set_len((num_blocks * self.chunk_size) as u64)?;
+        self.mmap = unsafe { MmapOptions::new().map_mut(&self.file)? };
+        self.num_blocks = num_blocks;
+        Ok(())
+    }
+
+    /// Truncates the backing file down to a single zeroed chunk.
+    pub(crate) fn reset(&mut self) -> io::Result<()> {
+        self.file.set_len(self.chunk_size as u64)?;
+        self.mmap = unsafe { MmapOptions::new().map_mut(&self.file)? };
+        self.mmap.fill(0);
+        self.num_blocks = 1;
+        Ok(())
+    }
+
+    pub(crate) fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Reads chunk `index` as a `BigUint`. Chunks past the mapped region read as zero.
+    pub(crate) fn chunk(&self, index: usize) -> BigUint {
+        if index >= self.num_blocks {
+            return BigUint::zero();
+        }
+        let offset = index * self.chunk_size;
+        BigUint::from_bytes_le(&self.mmap[offset..offset + self.chunk_size])
+    }
+
+    /// Iterates chunks from `from_index` down to `0`, most significant first.
+    /// Used by `modulo` to read the most significant chunk and to scan the
+    /// chunks below it for zeros. `modulo` builds a fresh iterator on each
+    /// pass, so the top chunk is still re-read on every pass.
+    pub(crate) fn iter_rev(&self, from_index: usize) -> impl Iterator<Item = BigUint> + '_ {
+        (0..=from_index).rev().map(move |i| self.chunk(i))
+    }
+
+    /// Writes `n` into chunk `index`, growing (and remapping) the file if needed.
+    pub(crate) fn write_chunk(&mut self, index: usize, n: &BigUint) -> io::Result<()> {
+        self.grow_to(index + 1)?;
+        let offset = index * self.chunk_size;
+        let data = n.to_bytes_le();
+        let slice = &mut self.mmap[offset..offset + self.chunk_size];
+        slice[..data.len()].copy_from_slice(&data);
+        slice[data.len()..].fill(0);
+        Ok(())
+    }
+
+    /// Flushes pending mmap writes to disk. Call before the rename-swap dance in `pepin_test`.
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.mmap.flush()
+    }
+}
+
+fn chunkify_number(num: &BigUint, block_index: usize, chunk_size: usize) -> BigUint {
+    // Slice out `chunk_size` little-endian bytes starting at `block_index *
+    // chunk_size`. Byte-granular rather than u32-digit-granular, since
+    // `chunk_size` isn't necessarily a multiple of 4 (small Fermat moduli
+    // need sub-word chunk sizes to land on a chunk boundary).
+    let bytes = num.to_bytes_le();
+    let start = block_index * chunk_size;
+    if start >= bytes.len() {
+        return BigUint::zero();
+    }
+    let end = (start + chunk_size).min(bytes.len());
+    BigUint::from_bytes_le(&bytes[start..end])
+}
+
+pub(crate) fn number_size(num: &BigUint, chunk_size: usize) -> usize {
+    num.to_bytes_le().len().div_ceil(chunk_size)
+}
+
+pub(crate) fn write_number_in_chunks(num: &BigUint, start_index: usize, store: &mut ChunkFile) -> io::Result<()> {
+    let chunk_size = store.chunk_size();
+    let size = number_size(num, chunk_size);
+    for i in 0..size {
+        let chunk = chunkify_number(num, i, chunk_size);
+        store.write_chunk(start_index + i, &chunk)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_number_in_chunks(start_index: usize, size: usize, store: &ChunkFile) -> BigUint {
+    let chunk_size = store.chunk_size();
+    let mut bytes: Vec<u8> = Vec::with_capacity(size * chunk_size);
+    for i in start_index..start_index + size {
+        let mut chunk_bytes = store.chunk(i).to_bytes_le();
+        chunk_bytes.resize(chunk_size, 0); // Pad with zeros to ensure consistent chunk size
+        bytes.extend_from_slice(&chunk_bytes);
+    }
+
+    BigUint::from_bytes_le(&bytes)
+}
+
+pub(crate) fn split_biguint(num: &BigUint, chunk_size: usize) -> (BigUint, BigUint) {
+    let base = BigUint::from(1u8) << (chunk_size * 8);
+    let lower = num.clone() % &base;
+    let upper = num.clone() / &base;
+    (lower, upper)
+}
@@ -0,0 +1,9 @@
+pub mod arithmetic;
+pub mod block_iterator;
+mod chunk_file;
+pub mod chunk_store;
+pub mod error;
+mod pepin;
+
+pub use error::{Error, Result};
+pub use pepin::pepin_test;
@@ -0,0 +1,333 @@
+use std::io;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rayon::prelude::*;
+
+use crate::chunk_file::{read_number_in_chunks, split_biguint, write_number_in_chunks, ChunkFile};
+use crate::error::{Error, Result};
+
+/// Largest chunk size `pepin_test` will pick, to bound memory use for large `n`.
+const MAX_CHUNK_SIZE: usize = 1024 * 1024; // 1 MiB
+
+fn rename_file(src_filename: &str, dst_filename: &str) -> io::Result<()> {
+    std::fs::rename(src_filename, dst_filename)
+}
+
+/// Picks a chunk size (in bytes) for Fermat index `n`.
+///
+/// `modulo` only works correctly when the Fermat modulus `2^(2^n) + 1` lands
+/// exactly on a chunk boundary, i.e. `chunk_size * 8` must divide `2^n`. Since
+/// `chunk_size` is a power of two, any power of two up to `2^(n-3)` bytes
+/// divides evenly; this picks the largest such size, capped at
+/// `MAX_CHUNK_SIZE` for performance on large `n`.
+fn chunk_size_for(n: u32) -> usize {
+    (1usize << (n - 3)).min(MAX_CHUNK_SIZE)
+}
+
+/// Number of output columns computed per batch in `square_number`. Bounds
+/// the squared-result memory held at once to a few thousand `BigUint`s
+/// regardless of operand size, instead of the full `2*size-1` columns.
+const SQUARE_BATCH_COLUMNS: usize = 4096;
+
+/// Squares the `size` chunks of `src` starting at `start_index`, writing the
+/// result into `dst` (chunk-indexed from 0). `src` and `dst` must share the
+/// same chunk size.
+///
+/// Processes output columns in batches of `SQUARE_BATCH_COLUMNS` so the
+/// expensive part can still run across threads without materializing the
+/// entire `2*size-1`-column result in RAM at once (the whole point of
+/// `ChunkFile` being disk-backed is to keep multi-GB intermediates off the
+/// heap):
+///
+/// Phase 1 computes each output column `k` (`0..=2*size-2`) independently as
+/// `col[k] = sum_{i+j=k} chunk_i * chunk_j`, doubling the product when
+/// `i != j`. Columns within a batch have no cross-column dependency, so
+/// they're computed with rayon into a `Vec<BigUint>` sized to the batch.
+///
+/// Phase 2 is a single-threaded left-to-right carry-normalization pass over
+/// that batch: for each `k`, add the running carry to `col[k]`, split it via
+/// `split_biguint` into `(lower, upper)`, write `lower` to output chunk `k`,
+/// and carry `upper` into the next batch. The carry can span more than one
+/// chunk, so once all batches are done this keeps going past the last
+/// column while `carry != 0`.
+fn square_number(src: &mut ChunkFile, start_index: usize, size: usize, dst: &mut ChunkFile) -> io::Result<usize> {
+    dst.reset()?;
+    let chunk_size = src.chunk_size();
+
+    let chunks: Vec<BigUint> = (0..size).map(|k| src.chunk(start_index + k)).collect();
+
+    let num_columns = 2 * size - 1;
+    let mut carry = BigUint::zero();
+    let mut final_size = 0;
+    let mut k = 0;
+    let mut batch_start = 0;
+    while batch_start < num_columns {
+        let batch_end = (batch_start + SQUARE_BATCH_COLUMNS).min(num_columns);
+        let batch: Vec<BigUint> = (batch_start..batch_end)
+            .into_par_iter()
+            .map(|k| {
+                let lo = k.saturating_sub(size - 1);
+                let hi = k.min(size - 1);
+                let mut col = BigUint::zero();
+                for i in lo..=hi {
+                    let j = k - i;
+                    if i > j {
+                        break;
+                    }
+                    let mut product = &chunks[i] * &chunks[j];
+                    if i != j {
+                        product *= 2u32;
+                    }
+                    col += product;
+                }
+                col
+            })
+            .collect();
+
+        for col in batch {
+            let value = &carry + &col;
+            let (lower, upper) = split_biguint(&value, chunk_size);
+            dst.write_chunk(k, &lower)?;
+            carry = upper;
+            final_size = k;
+            k += 1;
+        }
+
+        batch_start = batch_end;
+    }
+
+    while carry != BigUint::zero() {
+        let (lower, upper) = split_biguint(&carry, chunk_size);
+        dst.write_chunk(k, &lower)?;
+        carry = upper;
+        final_size = k;
+        k += 1;
+    }
+
+    Ok(final_size + 1)
+}
+
+/// Reduces the `size`-chunk number in `src` modulo the Fermat modulus
+/// `2^(max_size * chunk_size * 8) + 1`, returning the reduced chunk count.
+/// `minus_one` is the all-ones value of a single chunk, used when a borrow
+/// has to ripple through zero chunks.
+fn modulo(max_size: usize, minus_one: &BigUint, src: &mut ChunkFile, size: usize) -> io::Result<usize> {
+    let chunk_size = src.chunk_size();
+    let mut size_after = size;
+    while size_after > max_size {
+        // Stream the most significant chunk through the reverse iterator
+        // rather than indexing it directly, so the access pattern matches
+        // the all-zero scan below.
+        let msc = src.iter_rev(size_after - 1).next().unwrap();
+        if msc == BigUint::zero() {
+            size_after -= 1;
+            continue;
+        }
+        let to_subtract = msc.clone() - BigUint::one();
+        if to_subtract == BigUint::zero() {
+            if size_after - max_size == 1 {
+                // Go over the number to make sure at least one chunk is not zero, apart from the msc
+                let zeros = src.iter_rev(size_after - 2)
+                    .take(max_size)
+                    .all(|x| x == BigUint::zero());
+                if zeros {
+                    break;
+                } else {
+                    // Subtract 1 from the whole number, and take out the msc
+                    let mut borrow;
+                    let mut i = 0;
+                    while i < max_size {
+                        let mut x = src.chunk(size_after - 2 - i);
+                        if x == BigUint::zero() {
+                            x = minus_one.clone();
+                            borrow = BigUint::one();
+                        } else {
+                            x -= BigUint::one();
+                            borrow = BigUint::zero();
+                        }
+                        src.write_chunk(size_after - 2 - i, &x)?;
+                        if borrow == BigUint::zero() {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    src.write_chunk(size_after - 1, &BigUint::zero())?;
+                    size_after -= 1;
+                    break;
+                }
+            } else {
+                // The msc should be zero
+                src.write_chunk(size_after - 1, &BigUint::zero())?;
+
+                let val = src.chunk(size_after - 2) + BigUint::one();
+
+                // The second msc should be -1
+                src.write_chunk(size_after - 2, minus_one)?;
+
+                // Subtract val from the chunk `max_size` below the second msc
+                let mut borrow = BigUint::zero();
+                let mut i = 0;
+                while i == 0 || borrow != BigUint::zero() {
+                    let mut x = src.chunk(size_after - 2 + i - max_size);
+                    if i == 0 {
+                        if x >= val {
+                            x -= val.clone();
+                        } else {
+                            x = x.clone() + (BigUint::one() << (chunk_size * 8)) - val.clone();
+                            borrow = BigUint::one();
+                        }
+                    } else {
+                        if x == BigUint::zero() {
+                            x = minus_one.clone();
+                            borrow = BigUint::one();
+                        } else {
+                            x -= BigUint::one();
+                            borrow = BigUint::zero();
+                        }
+                    }
+                    src.write_chunk(size_after - 2 + i - max_size, &x)?;
+                    i += 1;
+                }
+            }
+        } else {
+            src.write_chunk(size_after - 1, &BigUint::one())?;
+
+            let sub_from = size_after - max_size - 1;
+
+            let mut borrow = BigUint::zero();
+            let mut i = 0;
+            while i == 0 || borrow != BigUint::zero() {
+                if i == size_after - 1 {
+                    size_after -= 1
+                }
+                let mut x = src.chunk(sub_from + i);
+                if i == 0 {
+                    if x >= to_subtract {
+                        x -= to_subtract.clone();
+                        borrow = BigUint::zero();
+                    } else {
+                        x = x.clone() + (BigUint::one() << (chunk_size * 8)) - to_subtract.clone();
+                        borrow = BigUint::one();
+                    }
+                } else {
+                    if x == BigUint::zero() {
+                        x = minus_one.clone();
+                        borrow = BigUint::one();
+                    } else {
+                        x -= BigUint::one();
+                        borrow = BigUint::zero();
+                    }
+                }
+                src.write_chunk(sub_from + i, &x)?;
+                i += 1;
+            }
+        }
+    }
+    Ok(size_after)
+}
+
+/// Runs the Pépin primality test on the Fermat number `F_n = 2^(2^n) + 1`:
+/// repeatedly squares and reduces `3` modulo `F_n` for `2^n - 1` rounds
+/// (computing `3^((F_n-1)/2) mod F_n`), then returns `true` iff the residue
+/// equals `F_n - 1`.
+///
+/// Requires `n >= 3`: the chunked modulus needs `2^n` to land on a whole-byte
+/// boundary, which is impossible for smaller `n`.
+pub fn pepin_test(n: u32) -> Result<bool> {
+    if n < 3 {
+        return Err(Error::from(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "pepin_test requires n >= 3 (the chunked modulus needs a whole-byte boundary)",
+        )));
+    }
+
+    let chunk_size = chunk_size_for(n);
+    let mod_bits = 1 + (1usize << n); // The number of bits in the Fermat number
+    let max_size = (mod_bits - 1) / (chunk_size * 8); // The number of chunks needed to store the operand
+
+    let minus_one: BigUint = (BigUint::from(1u32) << (chunk_size * 8)) - BigUint::one();
+    let num = BigUint::from(3u64);
+    let src_filename = "test1.dat";
+    let dst_filename = "test2.dat";
+    let temp_filename = "temp.dat";
+
+    let mut src = ChunkFile::open(src_filename, chunk_size, 1)?;
+    let mut dst = ChunkFile::open(dst_filename, chunk_size, 1)?;
+
+    write_number_in_chunks(&num, 0, &mut src)?;
+    let mut size = 1;
+    let start_index = 0;
+    let mut counter: usize = 0;
+    let total_rounds = 1usize << n;
+    let mut result = num;
+
+    loop {
+        counter += 1;
+        if counter == total_rounds {
+            break;
+        }
+        size = square_number(&mut src, start_index, size, &mut dst)?;
+
+        // Flush pending mmap writes before the rename-swap dance, then
+        // reopen under the swapped names.
+        src.flush()?;
+        dst.flush()?;
+        drop(src);
+        drop(dst);
+
+        rename_file(dst_filename, temp_filename)?;
+        rename_file(src_filename, dst_filename)?;
+        rename_file(temp_filename, src_filename)?;
+
+        src = ChunkFile::open(src_filename, chunk_size, size)?;
+        dst = ChunkFile::open(dst_filename, chunk_size, 1)?;
+
+        // NOTE: We're writing directly to the source file here, make sure this doesn't destroy anything
+        size = modulo(max_size, &minus_one, &mut src, size)?;
+        result = read_number_in_chunks(0, size, &src);
+    }
+    if size == 1 {
+        result = src.chunk(0);
+    }
+
+    let f_n_minus_one = BigUint::one() << (mod_bits - 1);
+    Ok(result == f_n_minus_one)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // All tests share the same chunk-file names: pepin_test always writes
+    // test1.dat/test2.dat/temp.dat in the current directory, so these tests
+    // must not run in parallel with
+    // each other. `cargo test` runs tests in the same crate on separate
+    // threads by default, so we serialize via a shared lock.
+    static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn cleanup() {
+        for f in ["test1.dat", "test2.dat", "temp.dat"] {
+            let _ = fs::remove_file(f);
+        }
+    }
+
+    #[test]
+    fn test_pepin_identifies_known_fermat_prime() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        cleanup();
+        // F_3 = 2^8 + 1 = 257, known prime.
+        assert!(pepin_test(3).unwrap());
+        cleanup();
+    }
+
+    #[test]
+    fn test_pepin_identifies_known_fermat_composite() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        cleanup();
+        // F_5 = 2^32 + 1 = 4294967297 = 641 * 6700417, known composite.
+        assert!(!pepin_test(5).unwrap());
+        cleanup();
+    }
+}